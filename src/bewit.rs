@@ -0,0 +1,135 @@
+//! Transparent bewit (signed-URL) authentication for safe HTTP methods.
+//!
+//! A bewit lets a client authenticate a plain `GET`/`HEAD` request entirely
+//! via a `bewit=` query parameter, with no `Authorization` header at all —
+//! handy for signed links. This module pulls that token out of a request URI,
+//! decodes it with `Bewit::from_str`, and rebuilds the URI without it, for
+//! [`HawkAuthService`](::HawkAuthService).
+
+use std::fmt;
+use std::str::FromStr;
+
+use hawk::Bewit;
+use hyper::header::{Formatter as HeaderFormatter, Header, Raw};
+use hyper::{Error as HyperError, Method, Result as HyperResult};
+
+/// Carries the `ext` field of a validated bewit through to the inner
+/// service. `hyper`'s `Request` in this version has no typed extensions
+/// map, so it rides along as a header instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BewitExt(pub String);
+
+impl Header for BewitExt {
+    fn header_name() -> &'static str {
+        "X-Hawk-Bewit-Ext"
+    }
+
+    fn parse_header(raw: &Raw) -> HyperResult<Self> {
+        raw.one()
+            .and_then(|line| ::std::str::from_utf8(line).ok())
+            .map(|s| BewitExt(s.to_string()))
+            .ok_or(HyperError::Header)
+    }
+
+    fn fmt_header(&self, f: &mut HeaderFormatter) -> fmt::Result {
+        f.fmt_line(&self.0)
+    }
+}
+
+/// Bewits only make sense for safe, side-effect-free requests.
+pub fn is_bewit_method(method: &Method) -> bool {
+    *method == Method::Get || *method == Method::Head
+}
+
+/// Pulls the `bewit` query parameter out of `uri`, returning the decoded
+/// bewit and the URI with that parameter removed, or `None` if `uri` carries
+/// no `bewit=` parameter (or its value fails to parse).
+///
+/// `Bewit::from_str` always decodes into owned data, regardless of the
+/// lifetime it's asked for, so the `Bewit` this returns carries no borrow of
+/// `uri` (or anything else) and can be held onto freely.
+pub fn extract_bewit(uri: &str) -> Option<(Bewit<'static>, String)> {
+    let (path, query) = match uri.find('?') {
+        Some(i) => (&uri[..i], &uri[i + 1..]),
+        None => return None,
+    };
+
+    let mut bewit_value = None;
+    let mut rest = Vec::new();
+    for pair in query.split('&') {
+        if bewit_value.is_none() && pair.starts_with("bewit=") {
+            bewit_value = Some(&pair["bewit=".len()..]);
+        } else if !pair.is_empty() {
+            rest.push(pair);
+        }
+    }
+
+    let bewit = Bewit::from_str(bewit_value?).ok()?;
+
+    let new_uri = if rest.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, rest.join("&"))
+    };
+
+    Some((bewit, new_uri))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_bewit;
+    use hawk::{Credentials, Key, RequestBuilder, SHA256};
+    use time;
+
+    fn credentials() -> Credentials {
+        Credentials {
+            id: "test-client".to_string(),
+            key: Key::new(vec![99u8; 32], &SHA256),
+        }
+    }
+
+    #[test]
+    fn extracts_bewit_and_strips_it_from_the_path() {
+        let credentials = credentials();
+        let bewit = RequestBuilder::new("GET", "example.com", 80, "/v1/users?foo=bar")
+            .ext("some-ext")
+            .request()
+            .make_bewit(&credentials, time::Duration::minutes(1))
+            .unwrap();
+
+        let uri = format!("/v1/users?bewit={}&foo=bar", bewit.to_str());
+        let (parsed, path) = extract_bewit(&uri).expect("bewit should be found");
+
+        assert_eq!(parsed.id(), "test-client");
+        assert_eq!(parsed.ext(), Some("some-ext"));
+        assert_eq!(path, "/v1/users?foo=bar");
+
+        let hawk_req = RequestBuilder::new("GET", "example.com", 80, &path).request();
+        assert!(hawk_req.validate_bewit(&parsed, &credentials.key));
+    }
+
+    #[test]
+    fn leaves_path_bare_when_bewit_is_the_only_query_param() {
+        let credentials = credentials();
+        let bewit = RequestBuilder::new("GET", "example.com", 80, "/v1/users")
+            .request()
+            .make_bewit(&credentials, time::Duration::minutes(1))
+            .unwrap();
+
+        let uri = format!("/v1/users?bewit={}", bewit.to_str());
+        let (_, path) = extract_bewit(&uri).expect("bewit should be found");
+
+        assert_eq!(path, "/v1/users");
+    }
+
+    #[test]
+    fn returns_none_without_a_bewit_param() {
+        assert!(extract_bewit("/v1/users?foo=bar").is_none());
+        assert!(extract_bewit("/v1/users").is_none());
+    }
+
+    #[test]
+    fn returns_none_for_an_unparseable_bewit_value() {
+        assert!(extract_bewit("/v1/users?bewit=not-a-real-bewit").is_none());
+    }
+}
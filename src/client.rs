@@ -0,0 +1,454 @@
+//! A `hyper::Client` wrapper that signs requests with Hawk and verifies the
+//! server's `Server-Authorization` response header automatically.
+//!
+//! Without this, callers have to build a `hawk::Request`, call
+//! `make_header`, set `Authorization`, then after the response manually pull
+//! `ServerAuthorization<HawkScheme>`, rebuild a response via
+//! `make_response_builder`, and call `validate_header` themselves. This does
+//! all of that end to end, including over TLS: [`HawkClientBuilder`] selects
+//! between a plain and an HTTPS-capable connector based on the scheme of the
+//! URL being requested.
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use futures::{Future, Poll, Stream};
+use hawk::{Credentials, PayloadHasher, RequestBuilder, SHA256};
+use hyper::client::{Connect, Service};
+use hyper::header::{Authorization, ContentType};
+use hyper::{self, Request, Uri};
+use tokio_io::{AsyncRead, AsyncWrite};
+
+use header::{HawkScheme, ServerAuthorization};
+
+/// Errors returned by [`HawkClient::send`].
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying `hyper::Client` request failed.
+    Hyper(hyper::Error),
+    /// The request's destination `Uri` carried no host, so no request could
+    /// be signed or sent.
+    InvalidUri,
+    /// The response had no `Server-Authorization` header to verify.
+    MissingServerAuthorization,
+    /// The `Server-Authorization` header failed Hawk validation. Also
+    /// returned, before anything is sent, when the outgoing request cannot
+    /// be signed.
+    InvalidServerAuthorization,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Hyper(ref e) => write!(f, "request failed: {}", e),
+            Error::InvalidUri => write!(f, "request URI carried no host"),
+            Error::MissingServerAuthorization => {
+                write!(f, "response carried no Server-Authorization header")
+            }
+            Error::InvalidServerAuthorization => {
+                write!(f, "Server-Authorization header failed validation")
+            }
+        }
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Error {
+        Error::Hyper(e)
+    }
+}
+
+/// Per-request options controlling which parts of the Hawk protocol a
+/// `HawkClient` request exercises.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestOptions {
+    /// Attach a payload hash for the request body being sent.
+    pub send_hash: bool,
+    /// Require (and verify) a payload hash on the response.
+    pub require_response_hash: bool,
+}
+
+impl RequestOptions {
+    /// The default options: no payload hashing either direction.
+    pub fn new() -> Self {
+        RequestOptions::default()
+    }
+
+    /// Attach a payload hash for the outgoing request body.
+    pub fn send_hash(mut self, send_hash: bool) -> Self {
+        self.send_hash = send_hash;
+        self
+    }
+
+    /// Require a payload hash on the response and verify it.
+    pub fn require_response_hash(mut self, require_response_hash: bool) -> Self {
+        self.require_response_hash = require_response_hash;
+        self
+    }
+}
+
+/// The port a `hawk::RequestBuilder` should assume for `uri` when it carries
+/// none explicitly: 443 for `https`, 80 otherwise.
+fn default_port(uri: &Uri) -> u16 {
+    if uri.scheme() == Some("https") {
+        443
+    } else {
+        80
+    }
+}
+
+/// A `hyper::Client` wrapper that transparently signs requests with Hawk and
+/// verifies the `Server-Authorization` header (and, optionally, the response
+/// payload hash) on the way back.
+pub struct HawkClient<C> {
+    client: hyper::Client<C>,
+    credentials: Arc<Credentials>,
+}
+
+impl<C> HawkClient<C>
+where
+    C: Connect,
+{
+    /// Wrap `client`, signing every request sent through `send` with
+    /// `credentials`.
+    ///
+    /// `hawk::Credentials` implements neither `Clone` nor `Debug`, so it's
+    /// kept behind an `Arc` here rather than cloned per-request.
+    pub fn new(client: hyper::Client<C>, credentials: Credentials) -> Self {
+        HawkClient {
+            client,
+            credentials: Arc::new(credentials),
+        }
+    }
+
+    /// Send `body` to `uri` as a signed Hawk request, returning the response
+    /// body once the `Server-Authorization` header (and payload hash, if
+    /// `options.require_response_hash`) has been verified.
+    pub fn send(
+        &self,
+        method: hyper::Method,
+        uri: Uri,
+        content_type: hyper::mime::Mime,
+        body: Vec<u8>,
+        options: RequestOptions,
+    ) -> Box<dyn Future<Item = Vec<u8>, Error = Error>> {
+        let credentials = self.credentials.clone();
+        let content_type_bytes = content_type.to_string().into_bytes();
+
+        let default_port = default_port(&uri);
+        let host = match uri.host() {
+            Some(host) => host.to_string(),
+            None => {
+                return Box::new(::futures::future::err(Error::InvalidUri));
+            }
+        };
+        let port = uri.port().unwrap_or(default_port);
+        let path = if uri.query().is_some() {
+            format!("{}?{}", uri.path(), uri.query().unwrap())
+        } else {
+            uri.path().to_string()
+        };
+        let method_str = method.as_ref().to_string();
+
+        // `hawk::RequestBuilder`/`Request` borrow from `method_str`/`host`/`path`,
+        // so rather than carry one across the request/response futures (which,
+        // boxed as `'static`, can't hold a borrow of these locals), it's
+        // rebuilt fresh wherever it's needed: once here, synchronously, to
+        // make the outgoing header, and once more below to make the response
+        // validator, with only the owned strings themselves moved into the
+        // closure in between.
+        let hawk_header = {
+            let send_hash = if options.send_hash {
+                Some(PayloadHasher::hash(&content_type_bytes[..], &SHA256, &body[..]))
+            } else {
+                None
+            };
+            let mut hawk_req_builder = RequestBuilder::new(&method_str, &host, port, &path);
+            if let Some(ref hash) = send_hash {
+                hawk_req_builder = hawk_req_builder.hash(&hash[..]);
+            }
+            match hawk_req_builder.request().make_header(&credentials) {
+                Ok(h) => h,
+                Err(_) => {
+                    return Box::new(::futures::future::err(Error::InvalidServerAuthorization));
+                }
+            }
+        };
+
+        let mut req = Request::new(method, uri);
+        req.headers_mut()
+            .set(Authorization(HawkScheme(hawk_header.clone())));
+        req.headers_mut().set(ContentType(content_type.clone()));
+        req.set_body(body);
+
+        let require_response_hash = options.require_response_hash;
+        Box::new(
+            self.client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(move |res| {
+                    let server_header = res
+                        .headers()
+                        .get::<ServerAuthorization<HawkScheme>>()
+                        .map(|h| (h.0).clone());
+                    let server_header = match server_header {
+                        Some(h) => h,
+                        None => {
+                            return Box::new(::futures::future::err(
+                                Error::MissingServerAuthorization,
+                            ))
+                                as Box<dyn Future<Item = Vec<u8>, Error = Error>>
+                        }
+                    };
+                    let response_content_type = res
+                        .headers()
+                        .get::<ContentType>()
+                        .map(|ct| ct.to_string())
+                        .unwrap_or_default()
+                        .into_bytes();
+
+                    Box::new(
+                        res.body()
+                            .concat2()
+                            .map_err(Error::from)
+                            .and_then(move |chunk| {
+                                let hash_bytes = if require_response_hash {
+                                    Some(PayloadHasher::hash(
+                                        &response_content_type[..],
+                                        &SHA256,
+                                        chunk.as_ref(),
+                                    ))
+                                } else {
+                                    None
+                                };
+
+                                let hawk_req =
+                                    RequestBuilder::new(&method_str, &host, port, &path).request();
+                                let mut resp_builder =
+                                    hawk_req.make_response_builder(&hawk_header);
+                                if let Some(ref hash) = hash_bytes {
+                                    resp_builder = resp_builder.hash(&hash[..]);
+                                }
+
+                                if !resp_builder
+                                    .response()
+                                    .validate_header(&server_header, &credentials.key)
+                                {
+                                    return Err(Error::InvalidServerAuthorization);
+                                }
+
+                                Ok(chunk.to_vec())
+                            }),
+                    )
+                }),
+        )
+    }
+}
+
+/// Builds a [`HawkClient`] over the connector appropriate for the scheme it
+/// will be used against, so callers can talk `https` without hand-rolling
+/// `hyper::Client::configure()` boilerplate.
+pub struct HawkClientBuilder {
+    handle: ::tokio_core::reactor::Handle,
+}
+
+impl HawkClientBuilder {
+    /// Start building a client that will run on `handle`'s reactor.
+    pub fn new(handle: ::tokio_core::reactor::Handle) -> Self {
+        HawkClientBuilder { handle }
+    }
+
+    /// Build a client that only ever talks plain `http://`.
+    pub fn http(self, credentials: Credentials) -> HawkClient<hyper::client::HttpConnector> {
+        let client = hyper::Client::new(&self.handle);
+        HawkClient::new(client, credentials)
+    }
+
+    /// Build a client able to talk both `http://` and `https://`, using
+    /// `https_connector` (e.g. `hyper_tls::HttpsConnector` or
+    /// `hyper_rustls::HttpsConnector`) for the latter.
+    pub fn https<C>(
+        self,
+        https_connector: C,
+        credentials: Credentials,
+    ) -> HawkClient<EitherConnector<hyper::client::HttpConnector, C>>
+    where
+        C: Connect,
+    {
+        let connector = EitherConnector::Https(
+            hyper::client::HttpConnector::new(4, &self.handle),
+            https_connector,
+        );
+        let client = hyper::Client::configure()
+            .connector(connector)
+            .build(&self.handle);
+        HawkClient::new(client, credentials)
+    }
+}
+
+/// A `Connect` that dispatches to a plain HTTP connector or an HTTPS
+/// connector depending on the scheme of the destination being connected to.
+pub enum EitherConnector<A, B> {
+    Http(A),
+    /// Carries the plain connector too, for `http://` requests made through
+    /// a client that was built for `https://`.
+    Https(A, B),
+}
+
+/// `Connect` is only ever obtained through the blanket impl over
+/// `Service<Request = Uri, Error = io::Error>` (there's no `Connect` to
+/// implement directly in this version of hyper), so that's what
+/// `EitherConnector` implements, the same way `HttpConnector` does.
+impl<A, B> Service for EitherConnector<A, B>
+where
+    A: Connect,
+    B: Connect,
+{
+    type Request = Uri;
+    type Response = MaybeHttpsStream<A::Output, B::Output>;
+    type Error = io::Error;
+    type Future = Box<dyn Future<Item = Self::Response, Error = io::Error>>;
+
+    fn call(&self, uri: Uri) -> Self::Future {
+        match *self {
+            EitherConnector::Http(ref http) => {
+                Box::new(http.connect(uri).map(MaybeHttpsStream::Http))
+            }
+            EitherConnector::Https(ref http, ref https) => {
+                if uri.scheme() == Some("https") {
+                    Box::new(https.connect(uri).map(MaybeHttpsStream::Https))
+                } else {
+                    Box::new(http.connect(uri).map(MaybeHttpsStream::Http))
+                }
+            }
+        }
+    }
+}
+
+/// The transport returned by [`EitherConnector`]: either the plain-HTTP
+/// transport or the HTTPS one, chosen per-connection by scheme.
+pub enum MaybeHttpsStream<A, B> {
+    Http(A),
+    Https(B),
+}
+
+impl<A: io::Read, B: io::Read> io::Read for MaybeHttpsStream<A, B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            MaybeHttpsStream::Http(ref mut s) => s.read(buf),
+            MaybeHttpsStream::Https(ref mut s) => s.read(buf),
+        }
+    }
+}
+
+impl<A: io::Write, B: io::Write> io::Write for MaybeHttpsStream<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            MaybeHttpsStream::Http(ref mut s) => s.write(buf),
+            MaybeHttpsStream::Https(ref mut s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            MaybeHttpsStream::Http(ref mut s) => s.flush(),
+            MaybeHttpsStream::Https(ref mut s) => s.flush(),
+        }
+    }
+}
+
+impl<A: AsyncRead, B: AsyncRead> AsyncRead for MaybeHttpsStream<A, B> {}
+
+impl<A: AsyncWrite, B: AsyncWrite> AsyncWrite for MaybeHttpsStream<A, B> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match *self {
+            MaybeHttpsStream::Http(ref mut s) => s.shutdown(),
+            MaybeHttpsStream::Https(ref mut s) => s.shutdown(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn default_port_is_443_for_https() {
+        let uri: Uri = "https://example.com/resource".parse().unwrap();
+        assert_eq!(default_port(&uri), 443);
+    }
+
+    #[test]
+    fn default_port_is_80_for_http() {
+        let uri: Uri = "http://example.com/resource".parse().unwrap();
+        assert_eq!(default_port(&uri), 80);
+    }
+
+    /// Wraps a connector, recording which one `EitherConnector` dispatched
+    /// to before delegating to it.
+    struct RecordingConnector<A> {
+        label: &'static str,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+        inner: A,
+    }
+
+    impl<A> Service for RecordingConnector<A>
+    where
+        A: Service<Request = Uri, Error = io::Error>,
+    {
+        type Request = Uri;
+        type Response = A::Response;
+        type Error = io::Error;
+        type Future = A::Future;
+
+        fn call(&self, uri: Uri) -> Self::Future {
+            self.calls.lock().unwrap().push(self.label);
+            self.inner.call(uri)
+        }
+    }
+
+    #[test]
+    fn either_connector_dispatches_by_scheme() {
+        let core = ::tokio_core::reactor::Core::new().unwrap();
+        let handle = core.handle();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let http = RecordingConnector {
+            label: "http",
+            calls: calls.clone(),
+            inner: hyper::client::HttpConnector::new(1, &handle),
+        };
+        let https = RecordingConnector {
+            label: "https",
+            calls: calls.clone(),
+            inner: hyper::client::HttpConnector::new(1, &handle),
+        };
+        let connector = EitherConnector::Https(http, https);
+
+        let _ = connector.call("http://127.0.0.1:1/".parse().unwrap());
+        let _ = connector.call("https://127.0.0.1:1/".parse().unwrap());
+
+        assert_eq!(*calls.lock().unwrap(), vec!["http", "https"]);
+    }
+
+    #[test]
+    fn either_connector_without_an_https_leg_always_uses_http() {
+        let core = ::tokio_core::reactor::Core::new().unwrap();
+        let handle = core.handle();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let http = RecordingConnector {
+            label: "http",
+            calls: calls.clone(),
+            inner: hyper::client::HttpConnector::new(1, &handle),
+        };
+        let connector: EitherConnector<_, hyper::client::HttpConnector> =
+            EitherConnector::Http(http);
+
+        let _ = connector.call("https://127.0.0.1:1/".parse().unwrap());
+
+        assert_eq!(*calls.lock().unwrap(), vec!["http"]);
+    }
+}
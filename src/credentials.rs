@@ -0,0 +1,60 @@
+//! Credential lookup for multi-client Hawk deployments.
+//!
+//! [`validate_header`] and friends in the `hawk` crate take a single `Key`,
+//! which is fine for a single well-known client but not for a service with
+//! many Hawk clients, each keyed by the `id` field of their header. This
+//! module lets a caller resolve that `id` to a `Key` at validation time.
+//!
+//! `hawk::Key` implements neither `Clone` nor `Debug`, so providers hand
+//! keys out wrapped in an `Arc` rather than by value.
+
+use hawk::Key;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Resolves a Hawk client id to the `Key` used to sign its requests.
+///
+/// Implementations may be as simple as an in-memory map or back onto a
+/// database; since a real lookup often involves I/O, `get_key` returns a
+/// future rather than the resolved value directly.
+pub trait CredentialProvider {
+    /// The future returned while looking up `id`.
+    type Future: ::futures::Future<Item = Option<Arc<Key>>, Error = ::hyper::Error>;
+
+    /// Look up the key for the client identified by `id`, resolving to
+    /// `None` if there is no such client.
+    fn get_key(&self, id: &str) -> Self::Future;
+}
+
+/// A `CredentialProvider` backed by a plain in-memory `HashMap`.
+///
+/// This is the common case for small or fixed sets of clients; larger
+/// deployments will usually implement `CredentialProvider` directly against
+/// their own client/key store.
+#[derive(Clone, Default)]
+pub struct HashMapCredentialProvider {
+    keys: HashMap<String, Arc<Key>>,
+}
+
+impl HashMapCredentialProvider {
+    /// Create an empty provider; add clients with `insert`.
+    pub fn new() -> Self {
+        HashMapCredentialProvider {
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Register the key for a client id, replacing any existing entry.
+    pub fn insert(&mut self, id: String, key: Key) -> &mut Self {
+        self.keys.insert(id, Arc::new(key));
+        self
+    }
+}
+
+impl CredentialProvider for HashMapCredentialProvider {
+    type Future = ::futures::future::FutureResult<Option<Arc<Key>>, ::hyper::Error>;
+
+    fn get_key(&self, id: &str) -> Self::Future {
+        ::futures::future::ok(self.keys.get(id).cloned())
+    }
+}
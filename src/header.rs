@@ -0,0 +1,100 @@
+//! Typed `hyper` headers for the Hawk authentication scheme.
+//!
+//! These wrap the [`hawk`](https://docs.rs/hawk) crate's own `Header` type so
+//! that it can be used with `hyper::header::Authorization` (for requests) and
+//! [`ServerAuthorization`] (for the `Server-Authorization` response header
+//! Hawk uses for response authentication).
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use hawk::Header as HawkHeader;
+use hyper::error::Error as HyperError;
+use hyper::header::{Formatter as HeaderFormatter, Header, Raw, Scheme};
+
+/// A `hyper::header::Scheme` for `Authorization: Hawk ...` headers.
+///
+/// This is a thin wrapper around [`hawk::Header`], which does the actual
+/// parsing and formatting of the `id`, `ts`, `nonce`, `mac`, `ext`, `hash`,
+/// `app`, and `dlg` fields. It derefs to that type so callers can read the
+/// Hawk fields directly off an `Authorization<HawkScheme>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HawkScheme(pub HawkHeader);
+
+impl Deref for HawkScheme {
+    type Target = HawkHeader;
+
+    fn deref(&self) -> &HawkHeader {
+        &self.0
+    }
+}
+
+impl Scheme for HawkScheme {
+    fn scheme() -> Option<&'static str> {
+        Some("Hawk")
+    }
+
+    fn fmt_scheme(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for HawkScheme {
+    type Err = HyperError;
+
+    fn from_str(s: &str) -> Result<Self, HyperError> {
+        s.parse::<HawkHeader>()
+            .map(HawkScheme)
+            .map_err(|_| HyperError::Header)
+    }
+}
+
+/// The `Server-Authorization` header.
+///
+/// Hawk servers that want the client to be able to authenticate a response
+/// send this header back alongside the body, generally built with
+/// `hawk::Request::make_response_builder`. It has the same `Hawk ...` scheme
+/// syntax as `Authorization`, just under a different header name, so it is
+/// generic over the same `Scheme` types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerAuthorization<S: Scheme>(pub S);
+
+impl<S: Scheme + 'static> Header for ServerAuthorization<S> {
+    fn header_name() -> &'static str {
+        "Server-Authorization"
+    }
+
+    fn parse_header(raw: &Raw) -> hyper::Result<Self> {
+        let line = raw
+            .one()
+            .ok_or(HyperError::Header)
+            .and_then(|line| ::std::str::from_utf8(line).map_err(|_| HyperError::Header))?;
+
+        let scheme_name = S::scheme().unwrap_or("");
+        if !line.starts_with(scheme_name)
+            || line.as_bytes().get(scheme_name.len()) != Some(&b' ')
+        {
+            return Err(HyperError::Header);
+        }
+        let value = &line[scheme_name.len() + 1..];
+
+        value
+            .parse::<S>()
+            .map(ServerAuthorization)
+            .map_err(|_| HyperError::Header)
+    }
+
+    fn fmt_header(&self, f: &mut HeaderFormatter) -> fmt::Result {
+        f.fmt_line(self)
+    }
+}
+
+impl<S: Scheme> fmt::Display for ServerAuthorization<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(scheme) = S::scheme() {
+            write!(f, "{} ", scheme)?;
+        }
+        self.0.fmt_scheme(f)
+    }
+}
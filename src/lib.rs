@@ -0,0 +1,29 @@
+//! Hyper integration for the [Hawk](https://github.com/hueniverse/hawk) HTTP
+//! authentication scheme, built on the [`hawk`](https://docs.rs/hawk) crate.
+//!
+//! This crate provides the typed headers (`HawkScheme`, `ServerAuthorization`)
+//! Hawk needs on top of `hyper::header::Authorization`, along with a
+//! `hyper::server::Service` middleware ([`HawkAuthService`]) and a client
+//! helper that handle the request/response signing and verification dance so
+//! callers don't have to reimplement it per-project.
+
+extern crate futures;
+extern crate hawk;
+extern crate hyper;
+extern crate time;
+extern crate tokio_core;
+extern crate tokio_io;
+
+mod bewit;
+mod client;
+mod credentials;
+mod header;
+mod nonce;
+mod service;
+
+pub use bewit::BewitExt;
+pub use client::{EitherConnector, Error, HawkClient, HawkClientBuilder, RequestOptions};
+pub use credentials::{CredentialProvider, HashMapCredentialProvider};
+pub use header::{HawkScheme, ServerAuthorization};
+pub use nonce::{MemoryNonceValidator, NonceValidator};
+pub use service::HawkAuthService;
@@ -0,0 +1,87 @@
+//! Replay protection for Hawk nonces.
+//!
+//! `validate_header`'s `Duration` window only bounds how stale a header can
+//! be; it does not stop an attacker from replaying a captured, still-fresh
+//! header. A [`NonceValidator`] records the `(id, ts, nonce)` triples seen
+//! within that window and rejects repeats.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Checks whether a Hawk header's nonce has already been seen, and records
+/// it for future checks.
+///
+/// `seen` takes the timestamp the client sent (`ts`, seconds since the Unix
+/// epoch) so implementations can evict entries once they fall outside the
+/// replay window without needing their own clock; callers pass the same
+/// `now` they used to validate the header's timestamp skew.
+///
+/// Lookups are async-capable (returning a future) since a shared store such
+/// as Redis will usually involve I/O.
+pub trait NonceValidator {
+    /// The future returned while recording and checking a nonce.
+    type Future: ::futures::Future<Item = bool, Error = ::hyper::Error>;
+
+    /// Record that `nonce` was used by client `id` at time `ts`, resolving
+    /// to `true` if this is the first time it has been seen within the
+    /// allowed skew, or `false` if it is a replay and the request should be
+    /// rejected.
+    fn seen(&self, id: &str, ts: i64, nonce: &str, now: i64) -> Self::Future;
+}
+
+/// An in-memory `NonceValidator` backed by a time-bucketed set of
+/// `(id, nonce)` pairs.
+///
+/// Entries are grouped into buckets by the header timestamp (`ts`, in
+/// seconds) they carry; on every insert, buckets older than `skew` are
+/// dropped, so memory use
+/// stays bounded by `skew` worth of traffic rather than growing forever.
+#[derive(Clone)]
+pub struct MemoryNonceValidator {
+    inner: Arc<MemoryNonceValidatorInner>,
+}
+
+struct MemoryNonceValidatorInner {
+    skew: Duration,
+    buckets: Mutex<HashMap<i64, HashSet<(String, String)>>>,
+}
+
+impl MemoryNonceValidator {
+    /// Create a validator that remembers nonces for `skew` (which should
+    /// match, or exceed, the `Duration` passed to `validate_header`).
+    pub fn new(skew: Duration) -> Self {
+        MemoryNonceValidator {
+            inner: Arc::new(MemoryNonceValidatorInner {
+                skew,
+                buckets: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    fn evict_before(buckets: &mut HashMap<i64, HashSet<(String, String)>>, cutoff: i64) {
+        buckets.retain(|bucket, _| *bucket >= cutoff);
+    }
+}
+
+impl NonceValidator for MemoryNonceValidator {
+    type Future = ::futures::future::FutureResult<bool, ::hyper::Error>;
+
+    fn seen(&self, id: &str, ts: i64, nonce: &str, now: i64) -> Self::Future {
+        let cutoff = now - self.inner.skew.as_secs() as i64;
+        let mut buckets = self.inner.buckets.lock().expect("nonce store lock poisoned");
+
+        Self::evict_before(&mut buckets, cutoff);
+
+        if ts < cutoff {
+            // Already outside the window `validate_header` will enforce
+            // separately; treat it as a replay rather than remembering it.
+            return ::futures::future::ok(false);
+        }
+
+        let key = (id.to_string(), nonce.to_string());
+        let bucket = buckets.entry(ts).or_default();
+        let first_seen = bucket.insert(key);
+        ::futures::future::ok(first_seen)
+    }
+}
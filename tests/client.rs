@@ -0,0 +1,155 @@
+extern crate futures;
+extern crate hawk;
+extern crate hyper;
+extern crate hyper_hawk;
+
+use futures::stream::Concat2;
+use futures::{Async, Future, Poll, Stream};
+use hawk::{Credentials, Key, RequestBuilder, SHA256};
+use hyper::header::{Authorization, ContentLength, Host};
+use hyper::server::{Http, Service};
+use hyper::{Body, Method, Request, Response};
+use hyper_hawk::{Error, HawkClientBuilder, HawkScheme, RequestOptions, ServerAuthorization};
+
+/// What the stub server does with the client's `Authorization` header once
+/// the request body has arrived.
+#[derive(Clone, Copy)]
+enum Mode {
+    /// Sign a `Server-Authorization` header the client's key can validate.
+    Valid,
+    /// Sign a `Server-Authorization` header with a key the client doesn't
+    /// hold, so validation on the client side fails.
+    WrongKey,
+    /// Send no `Server-Authorization` header at all.
+    Missing,
+}
+
+struct StubService {
+    mode: Mode,
+}
+
+impl Service for StubService {
+    type Request = Request;
+    type Response = Response;
+    type Error = hyper::Error;
+    type Future = StubFuture;
+
+    fn call(&self, req: Request) -> Self::Future {
+        let header = req
+            .headers()
+            .get::<Authorization<HawkScheme>>()
+            .map(|h| (**h).clone());
+        let (host, port) = match req.headers().get::<Host>() {
+            Some(host) => (host.hostname().to_string(), host.port().unwrap_or(80)),
+            None => ("localhost".to_string(), 80),
+        };
+
+        StubFuture {
+            mode: self.mode,
+            header,
+            method_str: req.method().as_ref().to_string(),
+            host,
+            port,
+            path: req.uri().path().to_string(),
+            body_stream: req.body().concat2(),
+        }
+    }
+}
+
+struct StubFuture {
+    mode: Mode,
+    header: Option<Authorization<HawkScheme>>,
+    method_str: String,
+    host: String,
+    port: u16,
+    path: String,
+    body_stream: Concat2<Body>,
+}
+
+impl Future for StubFuture {
+    type Item = Response;
+    type Error = hyper::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Async::NotReady = self.body_stream.poll()? {
+            return Ok(Async::NotReady);
+        }
+
+        let body = b"OK";
+        let mut resp = Response::new()
+            .with_header(ContentLength(body.len() as u64))
+            .with_body(body.as_ref());
+
+        let signing_key = match self.mode {
+            Mode::Missing => None,
+            Mode::Valid => Some(Key::new(vec![1u8; 32], &SHA256)),
+            Mode::WrongKey => Some(Key::new(vec![2u8; 32], &SHA256)),
+        };
+
+        if let Some(key) = signing_key {
+            let header = self.header.clone().expect("client should have signed the request");
+            let hawk_req =
+                RequestBuilder::new(&self.method_str, &self.host, self.port, &self.path).request();
+            let server_hdr = hawk_req
+                .make_response_builder(&header)
+                .response()
+                .make_header(&key)
+                .unwrap();
+            resp.headers_mut()
+                .set(ServerAuthorization(HawkScheme(server_hdr)));
+        }
+
+        Ok(Async::Ready(resp))
+    }
+}
+
+fn run(mode: Mode) -> Result<Vec<u8>, Error> {
+    let service_factory = move || Ok(StubService { mode });
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = Http::new().bind(&addr, service_factory).unwrap();
+    let local_address = server.local_addr().unwrap();
+
+    let credentials = Credentials {
+        id: "test-client".to_string(),
+        key: Key::new(vec![1u8; 32], &SHA256),
+    };
+    let url = format!("http://127.0.0.1:{}/resource", local_address.port())
+        .parse()
+        .unwrap();
+
+    let handle = server.handle();
+    let client = HawkClientBuilder::new(handle).http(credentials);
+
+    let fut = client
+        .send(
+            Method::Post,
+            url,
+            "text/plain".parse().unwrap(),
+            b"foo=bar".to_vec(),
+            RequestOptions::new(),
+        )
+        .then(|result| Ok::<_, ()>(result));
+    server.run_until(fut).unwrap()
+}
+
+#[test]
+fn accepts_a_valid_server_authorization_header() {
+    let body = run(Mode::Valid).expect("expected a successful send");
+    assert_eq!(body, b"OK");
+}
+
+#[test]
+fn rejects_a_server_authorization_header_signed_with_the_wrong_key() {
+    match run(Mode::WrongKey) {
+        Err(Error::InvalidServerAuthorization) => {}
+        other => panic!("expected InvalidServerAuthorization, got {:?}", other),
+    }
+}
+
+#[test]
+fn errors_when_the_server_authorization_header_is_missing() {
+    match run(Mode::Missing) {
+        Err(Error::MissingServerAuthorization) => {}
+        other => panic!("expected MissingServerAuthorization, got {:?}", other),
+    }
+}
@@ -0,0 +1,45 @@
+extern crate futures;
+extern crate hawk;
+extern crate hyper_hawk;
+
+use futures::Future;
+use hawk::{Key, SHA256};
+use hyper_hawk::{CredentialProvider, HashMapCredentialProvider};
+
+#[test]
+fn looks_up_a_registered_client() {
+    let mut provider = HashMapCredentialProvider::new();
+    provider.insert("client-a".to_string(), Key::new(vec![1u8; 32], &SHA256));
+
+    let key = provider.get_key("client-a").wait().unwrap();
+    assert!(key.is_some());
+}
+
+#[test]
+fn unknown_client_resolves_to_none() {
+    let provider = HashMapCredentialProvider::new();
+    let key = provider.get_key("nobody").wait().unwrap();
+    assert!(key.is_none());
+}
+
+#[test]
+fn re_inserting_a_client_replaces_its_key() {
+    let mut provider = HashMapCredentialProvider::new();
+    provider.insert("client-a".to_string(), Key::new(vec![1u8; 32], &SHA256));
+    let original_sig = provider
+        .get_key("client-a")
+        .wait()
+        .unwrap()
+        .unwrap()
+        .sign(b"data");
+
+    provider.insert("client-a".to_string(), Key::new(vec![2u8; 32], &SHA256));
+    let replaced_sig = provider
+        .get_key("client-a")
+        .wait()
+        .unwrap()
+        .unwrap()
+        .sign(b"data");
+
+    assert_ne!(original_sig, replaced_sig);
+}
@@ -0,0 +1,60 @@
+extern crate hawk;
+extern crate hyper;
+extern crate hyper_hawk;
+
+use hawk::{Credentials, Key, RequestBuilder, SHA256};
+use hyper::header::{Header, Raw};
+use hyper_hawk::{HawkScheme, ServerAuthorization};
+
+fn credentials() -> Credentials {
+    Credentials {
+        id: "test-client".to_string(),
+        key: Key::new(vec![99u8; 32], &SHA256),
+    }
+}
+
+fn header_value() -> String {
+    let credentials = credentials();
+    let header = RequestBuilder::new("GET", "example.com", 80, "/v1/users")
+        .request()
+        .make_header(&credentials)
+        .unwrap();
+    header.to_string()
+}
+
+fn make_header_line() -> String {
+    format!("Hawk {}", header_value())
+}
+
+#[test]
+fn parses_a_well_formed_server_authorization_header() {
+    let raw: Raw = make_header_line().as_str().into();
+    assert!(ServerAuthorization::<HawkScheme>::parse_header(&raw).is_ok());
+}
+
+#[test]
+fn rejects_a_scheme_name_that_is_only_a_prefix_match() {
+    // "HawkScheme" starts with "Hawk" as a string, but is not the "Hawk "
+    // scheme followed by a space; parse_header must not treat it as one.
+    let line = make_header_line().replacen("Hawk ", "HawkScheme ", 1);
+    let raw: Raw = line.as_str().into();
+    assert!(ServerAuthorization::<HawkScheme>::parse_header(&raw).is_err());
+}
+
+#[test]
+fn rejects_a_filler_byte_standing_in_for_the_separating_space() {
+    // "Hawk" followed by some other byte ("Q") in place of the space, then
+    // the header body, would, with a boundary check that only skips one byte
+    // unconditionally,
+    // land on a value slice that still parses as a valid Hawk header body.
+    // The separator itself must be checked, not just skipped.
+    let line = format!("HawkQ{}", header_value());
+    let raw: Raw = line.as_str().into();
+    assert!(ServerAuthorization::<HawkScheme>::parse_header(&raw).is_err());
+}
+
+#[test]
+fn rejects_a_header_with_no_content_after_the_scheme() {
+    let raw: Raw = "Hawk".into();
+    assert!(ServerAuthorization::<HawkScheme>::parse_header(&raw).is_err());
+}
@@ -0,0 +1,190 @@
+//! End-to-end coverage of [`HawkAuthService`] and [`HawkClient`] talking to
+//! each other over a real socket, the way `tests/clientserver.rs` covers the
+//! hand-rolled validator/header plumbing those two types replace.
+
+extern crate futures;
+extern crate hawk;
+extern crate hyper;
+extern crate hyper_hawk;
+extern crate time;
+
+use std::time::Duration;
+
+use futures::{Future, Stream};
+use hawk::{Credentials, Key, RequestBuilder, SHA256};
+use hyper::header::{Authorization, ContentLength, Host};
+use hyper::server::{Http, Service};
+use hyper::{Method, Request, Response};
+use hyper_hawk::{
+    HashMapCredentialProvider, HawkAuthService, HawkClientBuilder, HawkScheme, RequestOptions,
+    ServerAuthorization,
+};
+
+/// An inner handler standing in for a real application: it trusts that
+/// `HawkAuthService` has already validated the request, and just needs the
+/// client's own signed header to build the `Server-Authorization` response
+/// `HawkClient` expects back.
+struct EchoService;
+
+impl Service for EchoService {
+    type Request = Request;
+    type Response = Response;
+    type Error = hyper::Error;
+    type Future = futures::future::FutureResult<Response, hyper::Error>;
+
+    fn call(&self, req: Request) -> Self::Future {
+        let auth_header = req
+            .headers()
+            .get::<Authorization<HawkScheme>>()
+            .map(|h| (**h).clone());
+        let (host, port) = match req.headers().get::<Host>() {
+            Some(host) => (host.hostname().to_string(), host.port().unwrap_or(80)),
+            None => ("localhost".to_string(), 80),
+        };
+        let method_str = req.method().as_ref().to_string();
+        let path = req.uri().path().to_string();
+
+        let body = b"OK";
+        let mut resp = Response::new()
+            .with_header(ContentLength(body.len() as u64))
+            .with_body(body.as_ref());
+
+        if let Some(header) = auth_header {
+            let key = Key::new(vec![1u8; 32], &SHA256);
+            let hawk_req = RequestBuilder::new(&method_str, &host, port, &path).request();
+            let server_hdr = hawk_req
+                .make_response_builder(&header)
+                .response()
+                .make_header(&key)
+                .unwrap();
+            resp.headers_mut()
+                .set(ServerAuthorization(HawkScheme(server_hdr)));
+        }
+
+        futures::future::ok(resp)
+    }
+}
+
+fn provider() -> HashMapCredentialProvider {
+    let mut provider = HashMapCredentialProvider::new();
+    provider.insert("test-client".to_string(), Key::new(vec![1u8; 32], &SHA256));
+    provider
+}
+
+#[test]
+fn signed_round_trip_through_the_middleware_and_client() {
+    let service_factory = move || {
+        Ok(HawkAuthService::new(
+            EchoService,
+            provider(),
+            Duration::from_secs(60),
+        ))
+    };
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = Http::new().bind(&addr, service_factory).unwrap();
+    let local_address = server.local_addr().unwrap();
+
+    let credentials = Credentials {
+        id: "test-client".to_string(),
+        key: Key::new(vec![1u8; 32], &SHA256),
+    };
+    let url = format!("http://127.0.0.1:{}/resource", local_address.port())
+        .parse()
+        .unwrap();
+
+    let handle = server.handle();
+    let client = HawkClientBuilder::new(handle).http(credentials);
+
+    let client_fut = client
+        .send(
+            Method::Post,
+            url,
+            "text/plain".parse().unwrap(),
+            b"foo=bar".to_vec(),
+            RequestOptions::new(),
+        )
+        .map(|body| {
+            assert_eq!(body, b"OK");
+        })
+        .map_err(|e| {
+            panic!("send failed: {:?}", e);
+        });
+    server.run_until(client_fut).unwrap();
+}
+
+#[test]
+fn unsigned_requests_are_rejected_with_401() {
+    let service_factory = move || {
+        Ok(HawkAuthService::new(
+            EchoService,
+            provider(),
+            Duration::from_secs(60),
+        ))
+    };
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = Http::new().bind(&addr, service_factory).unwrap();
+    let local_address = server.local_addr().unwrap();
+
+    let url = format!("http://127.0.0.1:{}/resource", local_address.port())
+        .parse()
+        .unwrap();
+
+    let handle = server.handle();
+    let client = hyper::Client::new(&handle);
+    let client_fut = client
+        .request(Request::new(Method::Get, url))
+        .map(|res| {
+            assert_eq!(res.status(), hyper::StatusCode::Unauthorized);
+        })
+        .map_err(|e| {
+            panic!("{:?}", e);
+        });
+    server.run_until(client_fut).unwrap();
+}
+
+#[test]
+fn bewit_authenticated_get_is_accepted() {
+    let service_factory = move || {
+        Ok(
+            HawkAuthService::new(EchoService, provider(), Duration::from_secs(60))
+                .allow_bewit(true),
+        )
+    };
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = Http::new().bind(&addr, service_factory).unwrap();
+    let local_address = server.local_addr().unwrap();
+
+    let credentials = Credentials {
+        id: "test-client".to_string(),
+        key: Key::new(vec![1u8; 32], &SHA256),
+    };
+
+    let bewit = RequestBuilder::new("GET", "127.0.0.1", local_address.port(), "/resource")
+        .request()
+        .make_bewit(&credentials, time::Duration::seconds(60))
+        .unwrap();
+
+    let url = format!(
+        "http://127.0.0.1:{}/resource?bewit={}",
+        local_address.port(),
+        bewit.to_str()
+    )
+    .parse()
+    .unwrap();
+
+    let handle = server.handle();
+    let client = hyper::Client::new(&handle);
+    let client_fut = client
+        .request(Request::new(Method::Get, url))
+        .and_then(|res| {
+            assert_eq!(res.status(), hyper::Ok);
+            res.body().concat2()
+        })
+        .map(|body| {
+            assert_eq!(body.as_ref(), b"OK");
+        })
+        .map_err(|e| {
+            panic!("{:?}", e);
+        });
+    server.run_until(client_fut).unwrap();
+}
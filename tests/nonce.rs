@@ -0,0 +1,38 @@
+extern crate futures;
+extern crate hyper_hawk;
+
+use std::time::Duration;
+
+use futures::Future;
+use hyper_hawk::{MemoryNonceValidator, NonceValidator};
+
+#[test]
+fn first_use_of_a_nonce_is_accepted() {
+    let validator = MemoryNonceValidator::new(Duration::from_secs(60));
+    let first_seen = validator.seen("client", 1000, "nonce-1", 1000).wait().unwrap();
+    assert!(first_seen);
+}
+
+#[test]
+fn replaying_a_nonce_is_rejected() {
+    let validator = MemoryNonceValidator::new(Duration::from_secs(60));
+    assert!(validator.seen("client", 1000, "nonce-1", 1000).wait().unwrap());
+    assert!(!validator.seen("client", 1000, "nonce-1", 1000).wait().unwrap());
+}
+
+#[test]
+fn same_nonce_from_different_clients_is_not_a_replay() {
+    let validator = MemoryNonceValidator::new(Duration::from_secs(60));
+    assert!(validator.seen("client-a", 1000, "nonce-1", 1000).wait().unwrap());
+    assert!(validator.seen("client-b", 1000, "nonce-1", 1000).wait().unwrap());
+}
+
+#[test]
+fn a_nonce_outside_the_replay_window_is_rejected_without_being_remembered() {
+    let validator = MemoryNonceValidator::new(Duration::from_secs(60));
+    // ts is far enough behind `now` to already be outside the skew window.
+    assert!(!validator.seen("client", 0, "nonce-1", 1000).wait().unwrap());
+    // Had it been remembered, seeing it again with `now` back inside its
+    // window would report a replay.
+    assert!(validator.seen("client", 0, "nonce-1", 0).wait().unwrap());
+}